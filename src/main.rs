@@ -1,46 +1,147 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use colored::Colorize;
 use futures::executor::block_on;
 use futures::future::join_all;
 use futures::{join, FutureExt};
+use semver::{Version, VersionReq};
+use serde::Serialize;
 use serde_yaml::Value;
 use std::error::Error;
 use std::ffi::OsStr;
 use std::fmt::Display;
 use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::{env, fmt};
 
+const FLUTTER_REPO_URL: &str = "https://github.com/flutter/flutter.git";
+
 #[derive(Debug)]
 enum ShellError<T: Error> {
     OSNotSupported,
     CommandFailed(T),
+    NonZeroExit { command: String, output: String },
 }
 
 impl<T: Error> Display for ShellError<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "{:?}", self)
+        match self {
+            ShellError::NonZeroExit { command, output } => {
+                writeln!(f, "command `{command}` exited with a non-zero status: {output}")
+            }
+            other => writeln!(f, "{:?}", other),
+        }
     }
 }
 
 impl<T: Error> Error for ShellError<T> {}
 
+#[derive(Debug)]
+enum ChangeVersionError {
+    DirtyWorkingTree(Vec<String>),
+    CommandFailed(Box<dyn Error>),
+}
+
+impl fmt::Display for ChangeVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeVersionError::DirtyWorkingTree(paths) => {
+                writeln!(
+                    f,
+                    "Flutter SDK working tree has uncommitted changes, refusing to reset:"
+                )?;
+                for path in paths {
+                    writeln!(f, "  {path}")?;
+                }
+                write!(
+                    f,
+                    "Re-run with --force to discard them, or --stash to restore them after the checkout"
+                )
+            }
+            ChangeVersionError::CommandFailed(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for ChangeVersionError {}
+
+impl<T: Error + 'static> From<ShellError<T>> for ChangeVersionError {
+    fn from(e: ShellError<T>) -> Self {
+        ChangeVersionError::CommandFailed(Box::new(e))
+    }
+}
+
 trait Printable: fmt::Display {
     fn print(&self) {
         println!("{self}");
     }
 }
 
+static STRUCTURED_OUTPUT: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+fn set_structured_output(enabled: bool) {
+    STRUCTURED_OUTPUT.store(enabled, std::sync::atomic::Ordering::Relaxed);
+}
+
+fn structured_output() -> bool {
+    STRUCTURED_OUTPUT.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Progress/diagnostic chatter: goes to stdout in text mode, stderr once a
+/// structured `--format` is selected so stdout stays pure json/yaml.
+macro_rules! progress {
+    ($($arg:tt)*) => {{
+        if structured_output() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    }};
+}
+
 #[derive(Parser)]
-struct Args {
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    Yaml,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Sync the local Flutter SDK with the project's pubspec version (must be named; there is no default subcommand)
+    Sync(SyncArgs),
+    /// Report a toolchain version matrix (Flutter, Dart, Git, ...) without touching the checkout
+    Info(InfoArgs),
+    /// List installable Flutter versions from the upstream repo's remote tags
+    List(ListArgs),
+}
+
+#[derive(clap::Args)]
+struct SyncArgs {
     #[arg(short = 'd', long = "workingDirectory", value_hint = clap::ValueHint::DirPath)]
     working_dir: Option<PathBuf>,
     #[arg(short = 'v', long = "desiredVersion")]
     desired_version: Option<String>,
+    /// Discard any local changes in the Flutter SDK checkout instead of refusing to proceed
+    #[arg(long, conflicts_with = "stash")]
+    force: bool,
+    /// Stash local changes in the Flutter SDK checkout before switching version and restore them afterwards
+    #[arg(long)]
+    stash: bool,
+    /// Output format for status/outcome reporting. Progress messages move to stderr for json/yaml.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
 }
 
-impl fmt::Display for Args {
+impl fmt::Display for SyncArgs {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(
             f,
@@ -67,13 +168,27 @@ impl fmt::Display for Args {
     }
 }
 
-impl Printable for Args {}
+impl Printable for SyncArgs {}
+
+#[derive(clap::Args)]
+struct InfoArgs {
+    #[arg(short = 'd', long = "workingDirectory", value_hint = clap::ValueHint::DirPath)]
+    working_dir: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+struct ListArgs {
+    #[arg(short = 'd', long = "workingDirectory", value_hint = clap::ValueHint::DirPath)]
+    working_dir: Option<PathBuf>,
+}
 
 struct Status {
     project_version: Option<String>,
     flutter_version: Option<String>,
     flutter_path: Option<PathBuf>,
     flutter_root_path: Option<PathBuf>,
+    engine_version: Option<String>,
+    channel: Option<String>,
 }
 
 impl Status {
@@ -83,6 +198,8 @@ impl Status {
             flutter_version: None,
             flutter_path: None,
             flutter_root_path: None,
+            engine_version: None,
+            channel: None,
         }
     }
 
@@ -92,6 +209,8 @@ impl Status {
             async { self.flutter_version = get_flutter_version().await }.boxed(),
             async { self.flutter_path = get_flutter_path().await }.boxed(),
             async { self.flutter_root_path = get_flutter_root_path().await }.boxed(),
+            async { self.engine_version = get_engine_version().await }.boxed(),
+            async { self.channel = get_channel().await }.boxed(),
         ])
         .await;
     }
@@ -117,24 +236,91 @@ impl fmt::Display for Status {
                 .and_then(|path| path.to_str())
                 .unwrap_or("None")
         )?;
-        write!(
+        writeln!(
             f,
             "Flutter root path: {}",
             self.flutter_root_path
                 .as_ref()
                 .and_then(|path| path.to_str())
                 .unwrap_or("None")
-        )
+        )?;
+        writeln!(
+            f,
+            "Engine version: {}",
+            self.engine_version.as_deref().unwrap_or("None")
+        )?;
+        write!(f, "Channel: {}", self.channel.as_deref().unwrap_or("None"))
     }
 }
 
 impl Printable for Status {}
 
+#[derive(Serialize)]
+struct StatusReport<'a> {
+    project_version: Option<&'a str>,
+    flutter_version: Option<&'a str>,
+    flutter_path: Option<&'a Path>,
+    flutter_root_path: Option<&'a Path>,
+    engine_version: Option<&'a str>,
+    channel: Option<&'a str>,
+    satisfied: bool,
+}
+
+impl Status {
+    fn report(&self) -> StatusReport<'_> {
+        let satisfied = match (&self.project_version, &self.flutter_version) {
+            (Some(project_version), Some(installed)) => {
+                constraint_satisfied(project_version, installed)
+            }
+            _ => false,
+        };
+
+        StatusReport {
+            project_version: self.project_version.as_deref(),
+            flutter_version: self.flutter_version.as_deref(),
+            flutter_path: self.flutter_path.as_deref(),
+            flutter_root_path: self.flutter_root_path.as_deref(),
+            engine_version: self.engine_version.as_deref(),
+            channel: self.channel.as_deref(),
+            satisfied,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum SyncOutcome<'a> {
+    AlreadySynced {
+        status: StatusReport<'a>,
+    },
+    Switched {
+        from: Option<String>,
+        to: String,
+        status: StatusReport<'a>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+fn emit_outcome(format: OutputFormat, outcome: &SyncOutcome) {
+    match format {
+        OutputFormat::Text => {}
+        OutputFormat::Json => match serde_json::to_string_pretty(outcome) {
+            Ok(json) => println!("{json}"),
+            Err(e) => eprintln!("Could not serialize outcome as json: {e}"),
+        },
+        OutputFormat::Yaml => match serde_yaml::to_string(outcome) {
+            Ok(yaml) => println!("{yaml}"),
+            Err(e) => eprintln!("Could not serialize outcome as yaml: {e}"),
+        },
+    }
+}
+
 fn main() {
-    let args = Args::parse_from(env::args().collect::<Vec<String>>());
-    let future = run(&args);
+    let cli = Cli::parse_from(env::args().collect::<Vec<String>>());
 
-    block_on(future)
+    block_on(run(&cli))
 }
 
 trait ShellCommand {
@@ -163,6 +349,28 @@ async fn shell_run(
     shell_command: &str,
     cwd: Option<&PathBuf>,
     console_print: bool,
+) -> Result<String, ShellError<std::io::Error>> {
+    shell_run_impl(shell_command, cwd, console_print, false).await
+}
+
+/// Like `shell_run`, but treats a non-zero exit status as a hard error
+/// instead of silently returning whatever the command printed. Use this for
+/// commands whose success/failure a caller actually branches on (e.g. the
+/// working-tree dirty check and stash push/pop) rather than ones that are
+/// merely run for their visible side effects.
+async fn shell_run_checked(
+    shell_command: &str,
+    cwd: Option<&PathBuf>,
+    console_print: bool,
+) -> Result<String, ShellError<std::io::Error>> {
+    shell_run_impl(shell_command, cwd, console_print, true).await
+}
+
+async fn shell_run_impl(
+    shell_command: &str,
+    cwd: Option<&PathBuf>,
+    console_print: bool,
+    require_success: bool,
 ) -> Result<String, ShellError<std::io::Error>> {
     let mut command = match env::consts::OS {
         "windows" | "macos" | "linux" => Command::new_shell(shell_command),
@@ -179,19 +387,33 @@ async fn shell_run(
     }
 
     if console_print {
-        command.stdout(Stdio::inherit());
-        command.stderr(Stdio::inherit());
+        if structured_output() {
+            // Keep child command chatter out of a structured stdout stream.
+            command.stdout(Stdio::null());
+            command.stderr(Stdio::inherit());
+        } else {
+            command.stdout(Stdio::inherit());
+            command.stderr(Stdio::inherit());
+        }
     }
 
     let result = command.output();
 
     let output = result.map_err(|e| ShellError::CommandFailed(e))?;
+    let combined = String::from_utf8_lossy(&[output.stdout, output.stderr].concat()).to_string();
 
-    return Ok(String::from_utf8_lossy(&[output.stdout, output.stderr].concat()).to_string());
+    if require_success && !output.status.success() {
+        return Err(ShellError::NonZeroExit {
+            command: shell_command.to_string(),
+            output: combined,
+        });
+    }
+
+    Ok(combined)
 }
 
 async fn get_flutter_version() -> Option<String> {
-    println!("Getting flutter version...");
+    progress!("Getting flutter version...");
     let output = shell_run("flutter --version", None, false).await.ok()?;
 
     Some(
@@ -259,22 +481,90 @@ async fn get_git_command_path() -> Option<PathBuf> {
     }
 }
 
+fn get_flutter_root_env() -> Option<PathBuf> {
+    let root = env::var("FLUTTER_ROOT").ok()?;
+
+    if root.trim().is_empty() {
+        return None;
+    }
+
+    Some(PathBuf::from(root))
+}
+
 async fn get_flutter_path() -> Option<PathBuf> {
-    println!("Getting flutter path...");
+    if let Some(root) = get_flutter_root_env() {
+        return Some(root.join("bin"));
+    }
+
+    progress!("Getting flutter path...");
     let flutter_command_path = get_flutter_command_path().await?;
     let flutter_path = flutter_command_path.parent()?;
     Some(flutter_path.to_owned())
 }
 
 async fn get_flutter_root_path() -> Option<PathBuf> {
-    println!("Getting flutter root path...");
+    if let Some(root) = get_flutter_root_env() {
+        return Some(root);
+    }
+
+    progress!("Getting flutter root path...");
     let flutter_path = get_flutter_path().await?;
     let flutter_root_path = flutter_path.parent()?;
     Some(flutter_root_path.to_owned())
 }
 
+fn read_trimmed_file(path: &PathBuf) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let mut buf = String::new();
+    file.read_to_string(&mut buf).ok()?;
+
+    let trimmed = buf.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    Some(trimmed.to_string())
+}
+
+async fn get_engine_version() -> Option<String> {
+    let root = get_flutter_root_path().await?;
+    read_trimmed_file(&root.join("bin/internal/engine.version"))
+}
+
+async fn get_channel() -> Option<String> {
+    if let Some(channel) = get_channel_from_flutter_version().await {
+        return Some(channel);
+    }
+
+    // `flutter --version` can fail to run (e.g. Flutter not on PATH yet), so fall
+    // back to the release-candidate-branch file. Note its contents are a branch
+    // label (e.g. `flutter-3.27-candidate.0`), not a channel name, so this is only
+    // ever a best-effort fallback, not an equivalent source.
+    let root = get_flutter_root_path().await?;
+    read_trimmed_file(&root.join("bin/internal/release-candidate-branch.version"))
+}
+
+async fn get_channel_from_flutter_version() -> Option<String> {
+    let output = shell_run("flutter --version", None, false).await.ok()?;
+    // Real output looks like `Flutter 3.24.3 • channel stable • https://...`,
+    // with a lowercase "channel" separated from the rest by bullets, so the
+    // keyword is matched case-insensitively and the next word is taken.
+    let channel_line = output
+        .lines()
+        .find(|line| line.to_lowercase().contains("channel"))?;
+    let mut words = channel_line.split_whitespace();
+
+    while let Some(word) = words.next() {
+        if word.eq_ignore_ascii_case("channel") {
+            return words.next().map(|word| word.trim_matches(',').to_string());
+        }
+    }
+
+    None
+}
+
 async fn get_project_version() -> Option<String> {
-    println!("Getting project version...");
+    progress!("Getting project version...");
     let mut pubspec_file = File::open("pubspec.yaml").ok()?;
     let mut buf = String::new();
 
@@ -291,7 +581,214 @@ async fn get_project_version() -> Option<String> {
     )
 }
 
-async fn change_flutter_version(version: &str, status: &Status) -> Result<(), Box<dyn Error>> {
+enum VersionConstraint {
+    Any,
+    Exact(Version),
+    Range(VersionReq),
+    /// Could not be parsed as a `Version` or `VersionReq`, even after
+    /// normalizing pub's space-separated comparators. Must NOT be treated as
+    /// "always satisfied" -- that would silently skip syncing entirely.
+    Invalid,
+}
+
+/// Dart pub constraints join comparators with a space (`">=3.19.0 <4.0.0"`);
+/// the `semver` crate's `VersionReq` requires a comma (`">=3.19.0, <4.0.0"`).
+/// Rewrite the former into the latter so ordinary pubspec ranges parse.
+fn normalize_pub_constraint(raw: &str) -> String {
+    raw.split_whitespace().collect::<Vec<_>>().join(", ")
+}
+
+fn parse_constraint(raw: &str) -> VersionConstraint {
+    let trimmed = raw.trim();
+
+    if trimmed.is_empty() || trimmed == "any" {
+        return VersionConstraint::Any;
+    }
+
+    if let Ok(version) = Version::parse(trimmed) {
+        return VersionConstraint::Exact(version);
+    }
+
+    if let Ok(req) = VersionReq::parse(trimmed) {
+        return VersionConstraint::Range(req);
+    }
+
+    match VersionReq::parse(&normalize_pub_constraint(trimmed)) {
+        Ok(req) => VersionConstraint::Range(req),
+        Err(_) => VersionConstraint::Invalid,
+    }
+}
+
+fn constraint_satisfied(constraint: &str, installed: &str) -> bool {
+    let Ok(installed) = Version::parse(installed.trim()) else {
+        return false;
+    };
+
+    match parse_constraint(constraint) {
+        VersionConstraint::Any => true,
+        VersionConstraint::Exact(version) => version == installed,
+        // `VersionReq::matches` never matches a pre-release version against a
+        // plain range, so compare the release part only: a `3.29.0-0.1.pre`
+        // install should still satisfy `>=3.19.0, <4.0.0`.
+        VersionConstraint::Range(req) => {
+            let release = Version::new(installed.major, installed.minor, installed.patch);
+            req.matches(&release)
+        }
+        VersionConstraint::Invalid => false,
+    }
+}
+
+/// Lists every Flutter release tag as a sorted, de-duplicated set.
+async fn resolve_remote_tags(status: &Status) -> Vec<Version> {
+    let cwd = status.flutter_path.as_ref();
+
+    let output = shell_run(
+        &format!("git ls-remote --tags {FLUTTER_REPO_URL}"),
+        cwd,
+        false,
+    )
+    .await
+    .unwrap_or_default();
+
+    let tags: std::collections::BTreeSet<Version> = output
+        .lines()
+        .filter_map(|line| line.split('\t').nth(1))
+        .filter_map(|reference| reference.strip_prefix("refs/tags/"))
+        .filter_map(|tag| tag.strip_suffix("^{}").or(Some(tag)))
+        .filter_map(|tag| Version::parse(tag.trim_start_matches('v')).ok())
+        .collect();
+
+    tags.into_iter().collect()
+}
+
+#[derive(Debug)]
+struct VersionNotAvailable {
+    requested: String,
+    suggestions: Vec<String>,
+}
+
+impl fmt::Display for VersionNotAvailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Version \"{}\" is not available or not installed", self.requested)?;
+
+        if self.suggestions.is_empty() {
+            Ok(())
+        } else {
+            write!(f, " (did you mean: {}?)", self.suggestions.join(", "))
+        }
+    }
+}
+
+impl Error for VersionNotAvailable {}
+
+fn parse_major_minor(text: &str) -> Option<(u64, u64)> {
+    let trimmed = text.trim_start_matches(['^', '~', '=', '>', '<']);
+    let mut parts = trimmed.splitn(3, '.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().and_then(|minor| minor.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
+
+/// Ranks remote tags by closeness to `requested`, falling back to the newest
+/// releases overall when `requested` doesn't even parse as a bare version.
+fn nearest_versions(pool: &[Version], requested: &str, count: usize) -> Vec<Version> {
+    let mut pool = pool.to_vec();
+
+    match parse_major_minor(requested) {
+        Some((major, minor)) => pool.sort_by_key(|version| {
+            let major_diff = (version.major as i64 - major as i64).unsigned_abs();
+            let minor_diff = (version.minor as i64 - minor as i64).unsigned_abs();
+            (major_diff, minor_diff, std::cmp::Reverse(version.clone()))
+        }),
+        None => pool.sort_by(|a, b| b.cmp(a)),
+    }
+
+    pool.into_iter().take(count).collect()
+}
+
+/// Resolves a possibly-fuzzy version request (`3.22`, `^3.19.0`, `latest`, or
+/// an exact tag) to a concrete, installable Flutter version, reusing the
+/// remote tag listing from the `list` command. Emits candidate suggestions
+/// instead of letting `git checkout` fail opaquely when nothing matches.
+async fn resolve_target_version(
+    requested: &str,
+    status: &Status,
+) -> Result<String, VersionNotAvailable> {
+    // Channel names aren't versions at all -- `change_flutter_version` switches
+    // channel for these directly, so pass them through unresolved instead of
+    // treating them as an unparseable (and therefore unavailable) version.
+    if matches!(
+        requested.to_ascii_lowercase().as_str(),
+        "stable" | "beta" | "main" | "master"
+    ) {
+        return Ok(requested.to_ascii_lowercase());
+    }
+
+    if requested.eq_ignore_ascii_case("latest") {
+        return resolve_remote_tags(status)
+            .await
+            .into_iter()
+            .filter(|version| version.pre.is_empty())
+            .max()
+            .map(|version| version.to_string())
+            .ok_or_else(|| VersionNotAvailable {
+                requested: requested.to_string(),
+                suggestions: Vec::new(),
+            });
+    }
+
+    if let VersionConstraint::Exact(version) = parse_constraint(requested) {
+        return Ok(version.to_string());
+    }
+
+    let req = match parse_constraint(requested) {
+        VersionConstraint::Range(req) => Some(req),
+        VersionConstraint::Any | VersionConstraint::Exact(_) | VersionConstraint::Invalid => None,
+    };
+
+    let tags = resolve_remote_tags(status).await;
+
+    let best = req.as_ref().and_then(|req| {
+        tags.iter()
+            .filter(|version| version.pre.is_empty() && req.matches(version))
+            .max()
+    });
+
+    match best {
+        Some(version) => Ok(version.to_string()),
+        None => {
+            let stable: Vec<Version> = tags.into_iter().filter(|v| v.pre.is_empty()).collect();
+            Err(VersionNotAvailable {
+                requested: requested.to_string(),
+                suggestions: nearest_versions(&stable, requested, 3)
+                    .into_iter()
+                    .map(|version| version.to_string())
+                    .collect(),
+            })
+        }
+    }
+}
+
+/// Returns the dirty paths reported by `git status --porcelain`, or an empty
+/// Vec when the tree is clean. Propagates a real error (rather than treating
+/// it as clean) when the status check itself fails, so a broken repo/git
+/// invocation can never be mistaken for "nothing to lose".
+async fn working_tree_dirty_paths(path: Option<&PathBuf>) -> Result<Vec<String>, ChangeVersionError> {
+    let output = shell_run_checked("git status --porcelain", path, false).await?;
+
+    Ok(output
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+async fn change_flutter_version(
+    version: &str,
+    status: &Status,
+    force: bool,
+    stash: bool,
+) -> Result<(), ChangeVersionError> {
     match version {
         "stable" | "beta" | "main" | "master" => {
             shell_run(format!("flutter channel {}", version).as_ref(), None, true).await?;
@@ -300,10 +797,26 @@ async fn change_flutter_version(version: &str, status: &Status) -> Result<(), Bo
         _ => {}
     };
 
-    println!("Cleaning flutter working tree...");
+    progress!("Checking flutter working tree status...");
+    let dirty = working_tree_dirty_paths(status.flutter_path.as_ref()).await?;
+
+    let stashed = if !dirty.is_empty() && stash {
+        progress!("Stashing local changes before checkout...");
+        shell_run_checked("git stash push -u", status.flutter_path.as_ref(), true).await?;
+        true
+    } else if !dirty.is_empty() && force {
+        progress!("Discarding local changes in Flutter SDK (--force)...");
+        false
+    } else if !dirty.is_empty() {
+        return Err(ChangeVersionError::DirtyWorkingTree(dirty));
+    } else {
+        false
+    };
+
+    progress!("Cleaning flutter working tree...");
     shell_run("git reset --hard", status.flutter_path.as_ref(), true).await?;
 
-    println!("Checking out {version}...");
+    progress!("Checking out {version}...");
     shell_run("git fetch", status.flutter_path.as_ref(), true).await?;
     shell_run(
         &format!("git checkout {version}"),
@@ -312,13 +825,25 @@ async fn change_flutter_version(version: &str, status: &Status) -> Result<(), Bo
     )
     .await?;
 
-    println!("Cleaning flutter working tree...");
+    progress!("Cleaning flutter working tree...");
     shell_run("git reset --hard", status.flutter_path.as_ref(), true).await?;
 
+    if stashed {
+        progress!("Restoring stashed changes...");
+        shell_run_checked("git stash pop", status.flutter_path.as_ref(), true).await?;
+    }
+
     if version == "3.29.0" {
         //https://github.com/flutter/flutter/issues/163308#issuecomment-2661479464
-        println!("Applying workaround for Flutter 3.29.0...");
-        println!("Removing /engine/src/.gn...");
+        // Gated on the resolved version string, not `status.engine_version`: by
+        // this point `version` is always an exact, already-resolved Flutter tag
+        // (never a fuzzy request), so the comparison is already precise. Keying
+        // off the engine hash instead would need a hardcoded hash-to-release
+        // table anyway, which is no more reliable than this string and harder to
+        // keep in sync; `engine_version` stays a `Status`/`--format` reporting
+        // field rather than an input to this check.
+        progress!("Applying workaround for Flutter 3.29.0...");
+        progress!("Removing /engine/src/.gn...");
 
         match env::consts::OS {
             "windows" => {
@@ -341,7 +866,7 @@ async fn change_flutter_version(version: &str, status: &Status) -> Result<(), Bo
         };
     }
 
-    println!("Running flutter doctor...");
+    progress!("Running flutter doctor...");
     shell_run("flutter doctor", None, true).await?;
 
     shell_run("flutter clean", None, true).await?;
@@ -349,25 +874,49 @@ async fn change_flutter_version(version: &str, status: &Status) -> Result<(), Bo
     shell_run("flutter pub upgrade", None, true).await?;
 
     if env::consts::OS == "macos" {
-        println!("Running pod install...");
+        progress!("Running pod install...");
         shell_run("pod install", Some("./ios".into()).as_ref(), true).await?;
     }
-    println!("Completed.");
+    progress!("Completed.");
     Ok(())
 }
 
-async fn run(args: &Args) {
-    println!("Flutter checker rust version {}", env!("CARGO_PKG_VERSION"));
+async fn run(cli: &Cli) {
+    match &cli.command {
+        Commands::Sync(args) => {
+            set_structured_output(args.format != OutputFormat::Text);
+            run_sync(args).await
+        }
+        Commands::Info(args) => run_info(args).await,
+        Commands::List(args) => run_list(args).await,
+    }
+}
+
+async fn run_sync(args: &SyncArgs) {
+    let format = args.format;
+    progress!("Flutter checker rust version {}", env!("CARGO_PKG_VERSION"));
 
     let (flutter_command, git_command) = join!(get_flutter_command_path(), get_git_command_path());
 
     if flutter_command == None {
-        println!("Flutter not found. Please install it and add it to path");
+        progress!("Flutter not found. Please install it and add it to path");
+        emit_outcome(
+            format,
+            &SyncOutcome::Error {
+                message: "Flutter not found. Please install it and add it to path".to_string(),
+            },
+        );
         return;
     }
 
     if git_command == None {
-        println!("Git not found. Please install it and add it to path");
+        progress!("Git not found. Please install it and add it to path");
+        emit_outcome(
+            format,
+            &SyncOutcome::Error {
+                message: "Git not found. Please install it and add it to path".to_string(),
+            },
+        );
         return;
     }
 
@@ -375,46 +924,89 @@ async fn run(args: &Args) {
         Some(working_dir) => match env::set_current_dir(working_dir) {
             Ok(_) => {}
             Err(e) => {
-                print!("Could not set the working directory, make sure --workingDirectory (-d) is a correct path\n{}", e);
+                let message = format!("Could not set the working directory, make sure --workingDirectory (-d) is a correct path\n{e}");
+                progress!("{message}");
+                emit_outcome(format, &SyncOutcome::Error { message });
                 return;
             }
         },
         None => {
-            println!("No working directory specified, using current directory");
+            progress!("No working directory specified, using current directory");
         }
     };
 
     let current_dir = env::current_dir().unwrap();
 
-    println!("Current directory is: {}", current_dir.display());
+    progress!("Current directory is: {}", current_dir.display());
 
     let mut status = Status::new();
 
-    println!("Loading current status...");
+    progress!("Loading current status...");
     status.update().await;
 
-    status.print();
+    if format == OutputFormat::Text {
+        status.print();
+    }
 
     match &args.desired_version {
         Some(desired_version) if !desired_version.is_empty() => {
-            println!("Desired version: {desired_version}");
-
-            if desired_version == status.flutter_version.as_deref().unwrap_or("None") {
-                println!("Flutter version is already {desired_version}");
+            progress!("Desired version: {desired_version}");
+
+            let installed = status.flutter_version.as_deref().unwrap_or("");
+            if constraint_satisfied(desired_version, installed) {
+                progress!("Flutter version is already {installed}, satisfying {desired_version}");
+                emit_outcome(
+                    format,
+                    &SyncOutcome::AlreadySynced {
+                        status: status.report(),
+                    },
+                );
                 return;
             }
 
-            println!("Syncing flutter version with desired version...");
+            progress!("Syncing flutter version with desired version...");
 
-            match change_flutter_version(&desired_version, &status).await {
+            let target_version = match resolve_target_version(desired_version, &status).await {
+                Ok(target_version) => target_version,
+                Err(e) => {
+                    progress!("{e}");
+                    emit_outcome(
+                        format,
+                        &SyncOutcome::Error {
+                            message: e.to_string(),
+                        },
+                    );
+                    return;
+                }
+            };
+
+            let from = status.flutter_version.clone();
+
+            match change_flutter_version(&target_version, &status, args.force, args.stash).await {
                 Ok(_) => {}
                 Err(e) => {
-                    println!("Could not change flutter version\\nError: {}", e);
+                    progress!("Could not change flutter version\\nError: {}", e);
+                    emit_outcome(
+                        format,
+                        &SyncOutcome::Error {
+                            message: e.to_string(),
+                        },
+                    );
                     return;
                 }
             };
             status.update().await;
-            status.print();
+            if format == OutputFormat::Text {
+                status.print();
+            }
+            emit_outcome(
+                format,
+                &SyncOutcome::Switched {
+                    from,
+                    to: target_version,
+                    status: status.report(),
+                },
+            );
             return;
         }
         _ => {}
@@ -422,29 +1014,399 @@ async fn run(args: &Args) {
 
     match &status.project_version {
         Some(project_version) if !project_version.is_empty() => {
-            if project_version == status.flutter_version.as_deref().unwrap_or("None") {
-                println!("Flutter version is already {project_version}");
+            let installed = status.flutter_version.as_deref().unwrap_or("");
+            if constraint_satisfied(project_version, installed) {
+                progress!("Flutter version is already {installed}, satisfying {project_version}");
+                emit_outcome(
+                    format,
+                    &SyncOutcome::AlreadySynced {
+                        status: status.report(),
+                    },
+                );
                 return;
             }
-            println!("Flutter version is not synced with project version. Syncing...");
+            progress!("Flutter version is not synced with project version. Syncing...");
 
-            match change_flutter_version(&project_version, &status).await {
+            let target_version = match resolve_target_version(project_version, &status).await {
+                Ok(target_version) => target_version,
+                Err(e) => {
+                    progress!("{e}");
+                    emit_outcome(
+                        format,
+                        &SyncOutcome::Error {
+                            message: e.to_string(),
+                        },
+                    );
+                    return;
+                }
+            };
+
+            let from = status.flutter_version.clone();
+
+            match change_flutter_version(&target_version, &status, args.force, args.stash).await {
                 Ok(_) => {}
                 Err(e) => {
-                    println!("Could not change flutter version\\nError: {}", e);
+                    progress!("Could not change flutter version\\nError: {}", e);
+                    emit_outcome(
+                        format,
+                        &SyncOutcome::Error {
+                            message: e.to_string(),
+                        },
+                    );
                     return;
                 }
             };
             status.update().await;
-            status.print();
+            if format == OutputFormat::Text {
+                status.print();
+            }
+            emit_outcome(
+                format,
+                &SyncOutcome::Switched {
+                    from,
+                    to: target_version,
+                    status: status.report(),
+                },
+            );
             return;
         }
         _ => {
-            println!("No project version found. Please specify a version with --desiredVersion or set the project version in pubspec.yaml");
+            let message = "No project version found. Please specify a version with --desiredVersion or set the project version in pubspec.yaml".to_string();
+            progress!("{message}");
+            emit_outcome(format, &SyncOutcome::Error { message });
+        }
+    }
+}
+
+struct ToolVersion {
+    name: &'static str,
+    path: Option<PathBuf>,
+    version: Option<String>,
+}
+
+struct InfoReport {
+    project_version: Option<String>,
+    tools: Vec<ToolVersion>,
+}
+
+impl fmt::Display for InfoReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name_width = self.tools.iter().map(|tool| tool.name.len()).max().unwrap_or(0);
+
+        for tool in &self.tools {
+            let path = tool
+                .path
+                .as_ref()
+                .and_then(|path| path.to_str())
+                .unwrap_or("not found");
+            let version = tool.version.as_deref().unwrap_or("unknown");
+
+            let line = format!("{:<name_width$}  {:<12}  {path}", tool.name, version);
+
+            if tool.version.is_some() {
+                writeln!(f, "{}", line.green())?;
+            } else {
+                writeln!(f, "{}", line.red())?;
+            }
+
+            if tool.name == "flutter" {
+                if let (Some(project_version), Some(installed)) =
+                    (&self.project_version, &tool.version)
+                {
+                    if constraint_satisfied(project_version, installed) {
+                        writeln!(
+                            f,
+                            "{}",
+                            format!("  \u{2713} satisfies project constraint {project_version}")
+                                .green()
+                        )?;
+                    } else {
+                        writeln!(
+                            f,
+                            "{}",
+                            format!(
+                                "  \u{2717} project requires {project_version}, found {installed}"
+                            )
+                            .red()
+                        )?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Printable for InfoReport {}
+
+/// Extracts the first dotted numeric token (e.g. `3.19.0`) from a CLI tool's
+/// version banner, trimming wrapping punctuation like `v` or `(`.
+fn extract_version_token(text: &str) -> Option<String> {
+    text.split_whitespace()
+        .find(|token| {
+            token.contains('.') && token.chars().any(|c| c.is_ascii_digit())
+        })
+        .map(|token| {
+            token
+                .trim_matches(|c: char| !c.is_ascii_digit() && c != '.')
+                .to_string()
+        })
+        .filter(|token| !token.is_empty())
+}
+
+async fn get_command_path(command_name: &str) -> Option<PathBuf> {
+    match env::consts::OS {
+        "windows" => {
+            let output = shell_run(&format!("where {command_name}"), None, false)
+                .await
+                .ok()?;
+            let path = output.split('\n').next()?.trim();
+
+            if path.is_empty() {
+                return None;
+            }
+
+            Some(path.into())
+        }
+        "macos" | "linux" => {
+            let output = shell_run(&format!("which {command_name}"), None, false)
+                .await
+                .ok()?;
+            let path = output.trim();
+
+            if path.is_empty() {
+                return None;
+            }
+
+            Some(path.into())
+        }
+        _ => None,
+    }
+}
+
+async fn probe_flutter() -> ToolVersion {
+    let (path, version) = join!(get_flutter_command_path(), get_flutter_version());
+    ToolVersion {
+        name: "flutter",
+        path,
+        version,
+    }
+}
+
+async fn probe_dart() -> ToolVersion {
+    let (path, output) = join!(get_command_path("dart"), shell_run("dart --version", None, false));
+    ToolVersion {
+        name: "dart",
+        path,
+        version: output.ok().as_deref().and_then(extract_version_token),
+    }
+}
+
+async fn probe_git() -> ToolVersion {
+    let (path, output) = join!(get_git_command_path(), shell_run("git --version", None, false));
+    ToolVersion {
+        name: "git",
+        path,
+        version: output.ok().as_deref().and_then(extract_version_token),
+    }
+}
+
+async fn probe_macos_tools() -> Vec<ToolVersion> {
+    if env::consts::OS != "macos" {
+        return Vec::new();
+    }
+
+    let (pod_path, pod_output, xcode_path, xcode_output) = join!(
+        get_command_path("pod"),
+        shell_run("pod --version", None, false),
+        get_command_path("xcodebuild"),
+        shell_run("xcodebuild -version", None, false)
+    );
+
+    vec![
+        ToolVersion {
+            name: "pod",
+            path: pod_path,
+            version: pod_output.ok().as_deref().and_then(extract_version_token),
+        },
+        ToolVersion {
+            name: "xcodebuild",
+            path: xcode_path,
+            version: xcode_output.ok().as_deref().and_then(extract_version_token),
+        },
+    ]
+}
+
+async fn run_info(args: &InfoArgs) {
+    println!("Flutter checker rust version {}", env!("CARGO_PKG_VERSION"));
+
+    if let Some(working_dir) = &args.working_dir {
+        if let Err(e) = env::set_current_dir(working_dir) {
+            println!("Could not set the working directory, make sure --workingDirectory (-d) is a correct path\n{}", e);
             return;
         }
     }
 
-    // let test = shell_run("flutter --version", &None).await;
-    // println!("Test: {}", test);
+    let project_version = get_project_version().await;
+
+    let (flutter, dart, git, mut extra) =
+        join!(probe_flutter(), probe_dart(), probe_git(), probe_macos_tools());
+
+    let mut tools = vec![flutter, dart, git];
+    tools.append(&mut extra);
+
+    let report = InfoReport {
+        project_version,
+        tools,
+    };
+    report.print();
+}
+
+/// Prints versions grouped by major.minor, assuming `versions` is sorted ascending.
+fn print_versions_by_minor(versions: &[Version]) {
+    let mut groups: Vec<(u64, u64, Vec<&Version>)> = Vec::new();
+
+    for version in versions {
+        match groups.last_mut() {
+            Some((major, minor, group)) if *major == version.major && *minor == version.minor => {
+                group.push(version);
+            }
+            _ => groups.push((version.major, version.minor, vec![version])),
+        }
+    }
+
+    for (major, minor, group) in groups {
+        let joined = group
+            .iter()
+            .map(|version| version.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("  {major}.{minor}: {joined}");
+    }
+}
+
+async fn run_list(args: &ListArgs) {
+    if let Some(working_dir) = &args.working_dir {
+        if let Err(e) = env::set_current_dir(working_dir) {
+            println!("Could not set the working directory, make sure --workingDirectory (-d) is a correct path\n{}", e);
+            return;
+        }
+    }
+
+    let mut status = Status::new();
+    status.flutter_path = get_flutter_path().await;
+
+    println!("Fetching available Flutter versions...");
+    let tags = resolve_remote_tags(&status).await;
+
+    if tags.is_empty() {
+        println!("Could not list remote Flutter versions. Is git installed and is the Flutter repo reachable?");
+        return;
+    }
+
+    let (stable, prerelease): (Vec<Version>, Vec<Version>) =
+        tags.into_iter().partition(|version| version.pre.is_empty());
+
+    println!("Stable releases:");
+    print_versions_by_minor(&stable);
+
+    if !prerelease.is_empty() {
+        println!("\nPre-releases:");
+        print_versions_by_minor(&prerelease);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constraint_satisfied_handles_pub_space_separated_ranges() {
+        assert!(!constraint_satisfied(">=3.19.0 <4.0.0", "3.0.0"));
+        assert!(constraint_satisfied(">=3.19.0 <4.0.0", "3.22.0"));
+        assert!(!constraint_satisfied(">=3.19.0 <4.0.0", "4.0.0"));
+    }
+
+    #[test]
+    fn constraint_satisfied_matches_pre_release_installs_against_plain_ranges() {
+        assert!(constraint_satisfied(">=3.19.0, <4.0.0", "3.29.0-0.1.pre"));
+        assert!(constraint_satisfied("^3.22.0", "3.22.0-0.1.pre"));
+    }
+
+    #[test]
+    fn constraint_satisfied_handles_caret_and_tilde() {
+        assert!(constraint_satisfied("^3.22.0", "3.25.0"));
+        assert!(!constraint_satisfied("^3.22.0", "4.0.0"));
+        assert!(constraint_satisfied("~3.22.0", "3.22.5"));
+        assert!(!constraint_satisfied("~3.22.0", "3.23.0"));
+    }
+
+    #[test]
+    fn constraint_satisfied_handles_any_and_empty() {
+        assert!(constraint_satisfied("any", "1.2.3"));
+        assert!(constraint_satisfied("", "1.2.3"));
+    }
+
+    #[test]
+    fn constraint_satisfied_treats_exact_fast_path_as_equality() {
+        assert!(constraint_satisfied("3.19.0", "3.19.0"));
+        assert!(!constraint_satisfied("3.19.0", "3.19.1"));
+    }
+
+    #[test]
+    fn constraint_satisfied_rejects_unparseable_constraints_instead_of_any() {
+        assert!(!constraint_satisfied("not a version", "3.19.0"));
+    }
+
+    #[test]
+    fn parse_constraint_normalizes_pub_style_ranges() {
+        assert!(matches!(
+            parse_constraint(">=3.19.0 <4.0.0"),
+            VersionConstraint::Range(_)
+        ));
+    }
+
+    #[test]
+    fn parse_major_minor_parses_bare_and_prefixed_versions() {
+        assert_eq!(parse_major_minor("3.22"), Some((3, 22)));
+        assert_eq!(parse_major_minor("^3.22.0"), Some((3, 22)));
+        assert_eq!(parse_major_minor("3"), Some((3, 0)));
+        assert_eq!(parse_major_minor("not-a-version"), None);
+    }
+
+    #[test]
+    fn nearest_versions_prefers_closest_major_minor() {
+        let pool = vec![
+            Version::new(3, 16, 0),
+            Version::new(3, 22, 0),
+            Version::new(3, 22, 2),
+            Version::new(3, 29, 0),
+        ];
+
+        let nearest = nearest_versions(&pool, "3.22", 2);
+
+        assert_eq!(
+            nearest,
+            vec![Version::new(3, 22, 2), Version::new(3, 22, 0)]
+        );
+    }
+
+    #[test]
+    fn nearest_versions_falls_back_to_newest_when_unparseable() {
+        let pool = vec![Version::new(3, 16, 0), Version::new(3, 29, 0)];
+
+        let nearest = nearest_versions(&pool, "latest", 1);
+
+        assert_eq!(nearest, vec![Version::new(3, 29, 0)]);
+    }
+
+    #[test]
+    fn resolve_target_version_exact_fast_path_skips_network() {
+        let status = Status::new();
+
+        let resolved = futures::executor::block_on(resolve_target_version("3.19.0", &status));
+
+        assert_eq!(resolved.unwrap(), "3.19.0");
+    }
 }